@@ -0,0 +1,57 @@
+use std::io;
+use std::io::Write;
+
+// Writes one delimited record, quoting a field (and doubling embedded quotes)
+// whenever it contains the delimiter, a quote, or a line break.
+pub fn write_record<W: Write>(writer: &mut W, fields: &[String], delimiter: u8) -> io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(&[delimiter])?;
+        }
+        write_field(writer, field, delimiter)?;
+    }
+    writeln!(writer)
+}
+
+fn needs_quoting(field: &str, delimiter: u8) -> bool {
+    field.as_bytes().contains(&delimiter) || field.contains(['"', '\n', '\r'])
+}
+
+fn write_field<W: Write>(writer: &mut W, field: &str, delimiter: u8) -> io::Result<()> {
+    if needs_quoting(field, delimiter) {
+        write!(writer, "\"{}\"", field.replace('"', "\"\""))
+    } else {
+        write!(writer, "{}", field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(fields: &[&str], delimiter: u8) -> String {
+        let mut out = Vec::new();
+        write_record(
+            &mut out,
+            &fields.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+            delimiter,
+        )
+        .expect("write succeeds");
+        String::from_utf8(out).expect("valid utf-8")
+    }
+
+    #[test]
+    fn plain_fields_are_unquoted() {
+        assert_eq!(record(&["a", "b", "c"], b','), "a,b,c\n");
+    }
+
+    #[test]
+    fn fields_with_delimiter_or_quotes_are_quoted_and_escaped() {
+        assert_eq!(record(&["a,b", "say \"hi\""], b','), "\"a,b\",\"say \"\"hi\"\"\"\n");
+    }
+
+    #[test]
+    fn tsv_only_quotes_on_tab() {
+        assert_eq!(record(&["a,b", "c"], b'\t'), "a,b\tc\n");
+    }
+}
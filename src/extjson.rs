@@ -0,0 +1,527 @@
+use std::io;
+use std::io::Write;
+
+use bson::{RawArray, RawBsonRef, RawDocument};
+
+use serde_json::ser::{CharEscape, Formatter};
+
+/// Default ceiling on embedded document/array nesting, matching the default
+/// exposed as `--maxNestingDepth`.
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 1000;
+
+#[derive(Debug)]
+pub struct NestingDepthExceeded {
+    max_depth: usize,
+    object: String,
+}
+
+impl std::fmt::Display for NestingDepthExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "object at '{}' exceeds maximum nesting depth of {}",
+            self.object, self.max_depth
+        )
+    }
+}
+
+impl std::error::Error for NestingDepthExceeded {}
+
+// `path` identifies the offending object, e.g. `a.b.2.c`, by the dotted trail
+// of field names and array indices walked to reach the current depth.
+pub(crate) fn check_depth(depth: usize, max_depth: usize, path: &[String]) -> io::Result<()> {
+    if depth > max_depth {
+        let object = if path.is_empty() {
+            "<root>".to_string()
+        } else {
+            path.join(".")
+        };
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            NestingDepthExceeded { max_depth, object },
+        ));
+    }
+    Ok(())
+}
+
+// Writes extended JSON directly from the raw BSON element tree, without ever
+// materializing an intermediate `bson::Bson` or `serde_json::Value` tree.
+pub fn write_document<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    document: &RawDocument,
+    relaxed: bool,
+    max_depth: usize,
+) -> io::Result<()> {
+    let mut path = Vec::new();
+    write_document_at_depth(writer, formatter, document, relaxed, max_depth, 0, &mut path)
+}
+
+fn write_document_at_depth<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    document: &RawDocument,
+    relaxed: bool,
+    max_depth: usize,
+    depth: usize,
+    path: &mut Vec<String>,
+) -> io::Result<()> {
+    check_depth(depth, max_depth, path)?;
+    formatter.begin_object(writer)?;
+    for (i, element) in document.into_iter().enumerate() {
+        let (name, bson_ref) = element.map_err(to_io_error)?;
+        formatter.begin_object_key(writer, i == 0)?;
+        write_json_string(writer, formatter, name)?;
+        formatter.end_object_key(writer)?;
+        formatter.begin_object_value(writer)?;
+        path.push(name.to_string());
+        let result = write_bson_ref(writer, formatter, &bson_ref, relaxed, max_depth, depth, path);
+        path.pop();
+        result?;
+        formatter.end_object_value(writer)?;
+    }
+    formatter.end_object(writer)
+}
+
+fn write_array<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    array: &RawArray,
+    relaxed: bool,
+    max_depth: usize,
+    depth: usize,
+    path: &mut Vec<String>,
+) -> io::Result<()> {
+    check_depth(depth, max_depth, path)?;
+    formatter.begin_array(writer)?;
+    for (i, element) in array.into_iter().enumerate() {
+        let bson_ref = element.map_err(to_io_error)?;
+        formatter.begin_array_value(writer, i == 0)?;
+        path.push(i.to_string());
+        let result = write_bson_ref(writer, formatter, &bson_ref, relaxed, max_depth, depth, path);
+        path.pop();
+        result?;
+        formatter.end_array_value(writer)?;
+    }
+    formatter.end_array(writer)
+}
+
+fn write_bson_ref<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    bson_ref: &RawBsonRef,
+    relaxed: bool,
+    max_depth: usize,
+    depth: usize,
+    path: &mut Vec<String>,
+) -> io::Result<()> {
+    match bson_ref {
+        RawBsonRef::Double(value) => write_double(writer, formatter, *value, relaxed),
+        RawBsonRef::String(value) => write_json_string(writer, formatter, value),
+        RawBsonRef::Array(array) => {
+            write_array(writer, formatter, array, relaxed, max_depth, depth + 1, path)
+        }
+        RawBsonRef::Document(document) => write_document_at_depth(
+            writer,
+            formatter,
+            document,
+            relaxed,
+            max_depth,
+            depth + 1,
+            path,
+        ),
+        RawBsonRef::Boolean(value) => formatter.write_bool(writer, *value),
+        RawBsonRef::Null => formatter.write_null(writer),
+        RawBsonRef::RegularExpression(regex) => with_wrapper_object(
+            writer,
+            formatter,
+            "$regularExpression",
+            |writer, formatter| {
+                write_sub_object(
+                    writer,
+                    formatter,
+                    &[("pattern", regex.pattern), ("options", regex.options)],
+                )
+            },
+        ),
+        RawBsonRef::JavaScriptCode(code) => {
+            with_wrapper_object(writer, formatter, "$code", |writer, formatter| {
+                write_json_string(writer, formatter, code)
+            })
+        }
+        RawBsonRef::JavaScriptCodeWithScope(cws) => {
+            formatter.begin_object(writer)?;
+            formatter.begin_object_key(writer, true)?;
+            write_json_string(writer, formatter, "$code")?;
+            formatter.end_object_key(writer)?;
+            formatter.begin_object_value(writer)?;
+            write_json_string(writer, formatter, cws.code)?;
+            formatter.end_object_value(writer)?;
+            formatter.begin_object_key(writer, false)?;
+            write_json_string(writer, formatter, "$scope")?;
+            formatter.end_object_key(writer)?;
+            formatter.begin_object_value(writer)?;
+            path.push("$scope".to_string());
+            let result = write_document_at_depth(
+                writer,
+                formatter,
+                cws.scope,
+                relaxed,
+                max_depth,
+                depth + 1,
+                path,
+            );
+            path.pop();
+            result?;
+            formatter.end_object_value(writer)?;
+            formatter.end_object(writer)
+        }
+        RawBsonRef::Int32(value) => write_int(writer, formatter, "$numberInt", *value as i64, relaxed),
+        RawBsonRef::Int64(value) => write_int(writer, formatter, "$numberLong", *value, relaxed),
+        RawBsonRef::Timestamp(timestamp) => {
+            with_wrapper_object(writer, formatter, "$timestamp", |writer, formatter| {
+                formatter.begin_object(writer)?;
+                formatter.begin_object_key(writer, true)?;
+                write_json_string(writer, formatter, "t")?;
+                formatter.end_object_key(writer)?;
+                formatter.begin_object_value(writer)?;
+                formatter.write_u64(writer, timestamp.time as u64)?;
+                formatter.end_object_value(writer)?;
+                formatter.begin_object_key(writer, false)?;
+                write_json_string(writer, formatter, "i")?;
+                formatter.end_object_key(writer)?;
+                formatter.begin_object_value(writer)?;
+                formatter.write_u64(writer, timestamp.increment as u64)?;
+                formatter.end_object_value(writer)?;
+                formatter.end_object(writer)
+            })
+        }
+        RawBsonRef::Binary(binary) => {
+            with_wrapper_object(writer, formatter, "$binary", |writer, formatter| {
+                write_sub_object(
+                    writer,
+                    formatter,
+                    &[
+                        ("base64", &base64_encode(binary.bytes) as &str),
+                        ("subType", &format!("{:02x}", u8::from(binary.subtype))),
+                    ],
+                )
+            })
+        }
+        RawBsonRef::ObjectId(oid) => {
+            with_wrapper_object(writer, formatter, "$oid", |writer, formatter| {
+                write_json_string(writer, formatter, &oid.to_hex())
+            })
+        }
+        RawBsonRef::DateTime(date_time) => write_date_time(writer, formatter, *date_time, relaxed),
+        RawBsonRef::Symbol(symbol) => {
+            with_wrapper_object(writer, formatter, "$symbol", |writer, formatter| {
+                write_json_string(writer, formatter, symbol)
+            })
+        }
+        RawBsonRef::Decimal128(decimal) => {
+            with_wrapper_object(writer, formatter, "$numberDecimal", |writer, formatter| {
+                write_json_string(writer, formatter, &decimal.to_string())
+            })
+        }
+        RawBsonRef::Undefined => {
+            with_wrapper_object(writer, formatter, "$undefined", |writer, formatter| {
+                formatter.write_bool(writer, true)
+            })
+        }
+        RawBsonRef::MaxKey => with_wrapper_object(writer, formatter, "$maxKey", |writer, formatter| {
+            formatter.write_i64(writer, 1)
+        }),
+        RawBsonRef::MinKey => with_wrapper_object(writer, formatter, "$minKey", |writer, formatter| {
+            formatter.write_i64(writer, 1)
+        }),
+        RawBsonRef::DbPointer(db_pointer) => {
+            with_wrapper_object(writer, formatter, "$dbPointer", |writer, formatter| {
+                formatter.begin_object(writer)?;
+                formatter.begin_object_key(writer, true)?;
+                write_json_string(writer, formatter, "$ref")?;
+                formatter.end_object_key(writer)?;
+                formatter.begin_object_value(writer)?;
+                write_json_string(writer, formatter, db_pointer.namespace)?;
+                formatter.end_object_value(writer)?;
+                formatter.begin_object_key(writer, false)?;
+                write_json_string(writer, formatter, "$id")?;
+                formatter.end_object_key(writer)?;
+                formatter.begin_object_value(writer)?;
+                with_wrapper_object(writer, formatter, "$oid", |writer, formatter| {
+                    write_json_string(writer, formatter, &db_pointer.id.to_hex())
+                })?;
+                formatter.end_object_value(writer)?;
+                formatter.end_object(writer)
+            })
+        }
+    }
+}
+
+// Emits `{ "<key>": <value written by `write_value`> }`.
+fn with_wrapper_object<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    key: &str,
+    write_value: impl FnOnce(&mut W, &mut F) -> io::Result<()>,
+) -> io::Result<()> {
+    formatter.begin_object(writer)?;
+    formatter.begin_object_key(writer, true)?;
+    write_json_string(writer, formatter, key)?;
+    formatter.end_object_key(writer)?;
+    formatter.begin_object_value(writer)?;
+    write_value(writer, formatter)?;
+    formatter.end_object_value(writer)?;
+    formatter.end_object(writer)
+}
+
+fn write_sub_object<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    fields: &[(&str, &str)],
+) -> io::Result<()> {
+    formatter.begin_object(writer)?;
+    for (i, (key, value)) in fields.iter().enumerate() {
+        formatter.begin_object_key(writer, i == 0)?;
+        write_json_string(writer, formatter, key)?;
+        formatter.end_object_key(writer)?;
+        formatter.begin_object_value(writer)?;
+        write_json_string(writer, formatter, value)?;
+        formatter.end_object_value(writer)?;
+    }
+    formatter.end_object(writer)
+}
+
+fn write_int<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    canonical_key: &str,
+    value: i64,
+    relaxed: bool,
+) -> io::Result<()> {
+    if relaxed {
+        formatter.write_i64(writer, value)
+    } else {
+        with_wrapper_object(writer, formatter, canonical_key, |writer, formatter| {
+            write_json_string(writer, formatter, &value.to_string())
+        })
+    }
+}
+
+fn write_double<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    value: f64,
+    relaxed: bool,
+) -> io::Result<()> {
+    if relaxed && value.is_finite() {
+        formatter.write_f64(writer, value)
+    } else {
+        with_wrapper_object(writer, formatter, "$numberDouble", |writer, formatter| {
+            write_json_string(writer, formatter, &format_double(value))
+        })
+    }
+}
+
+fn format_double(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 {
+            "Infinity".to_string()
+        } else {
+            "-Infinity".to_string()
+        }
+    } else {
+        // `to_string()` already gives the shortest round-trip representation
+        // in plain decimal notation (never an exponent, so `1e40` renders as
+        // all of its digits); any integral value, like `1` or `1e40`, needs a
+        // trailing `.0` appended to read back as a double rather than an int.
+        let rendered = value.to_string();
+        if rendered.contains('.') || rendered.contains('e') || rendered.contains('E') {
+            rendered
+        } else {
+            format!("{}.0", rendered)
+        }
+    }
+}
+
+fn write_date_time<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    date_time: bson::DateTime,
+    relaxed: bool,
+) -> io::Result<()> {
+    if relaxed {
+        if let Ok(rfc3339) = date_time.try_to_rfc3339_string() {
+            return with_wrapper_object(writer, formatter, "$date", |writer, formatter| {
+                write_json_string(writer, formatter, &rfc3339)
+            });
+        }
+    }
+    with_wrapper_object(writer, formatter, "$date", |writer, formatter| {
+        write_int(
+            writer,
+            formatter,
+            "$numberLong",
+            date_time.timestamp_millis(),
+            false,
+        )
+    })
+}
+
+fn char_escape(byte: u8) -> Option<CharEscape> {
+    match byte {
+        b'"' => Some(CharEscape::Quote),
+        b'\\' => Some(CharEscape::ReverseSolidus),
+        0x08 => Some(CharEscape::Backspace),
+        0x0c => Some(CharEscape::FormFeed),
+        b'\n' => Some(CharEscape::LineFeed),
+        b'\r' => Some(CharEscape::CarriageReturn),
+        b'\t' => Some(CharEscape::Tab),
+        0x00..=0x1f => Some(CharEscape::AsciiControl(byte)),
+        _ => None,
+    }
+}
+
+fn write_json_string<W: Write, F: Formatter>(
+    writer: &mut W,
+    formatter: &mut F,
+    value: &str,
+) -> io::Result<()> {
+    formatter.begin_string(writer)?;
+    let bytes = value.as_bytes();
+    let mut start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        if let Some(escape) = char_escape(byte) {
+            if start < i {
+                formatter.write_string_fragment(writer, &value[start..i])?;
+            }
+            formatter.write_char_escape(writer, escape)?;
+            start = i + 1;
+        }
+    }
+    if start < bytes.len() {
+        formatter.write_string_fragment(writer, &value[start..])?;
+    }
+    formatter.end_string(writer)
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = *chunk.get(1).unwrap_or(&0);
+        let b2 = *chunk.get(2).unwrap_or(&0);
+
+        out.push(BASE64_ALPHABET[(b0 >> 2) as usize] as char);
+        out.push(BASE64_ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64_ALPHABET[(b2 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+pub(crate) fn to_io_error(error: bson::raw::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+// Renders a single BSON value for a tabular (CSV/TSV) cell: strings are
+// emitted as plain text, everything else as its compact relaxed-extjson form.
+pub(crate) fn render_field(bson_ref: &RawBsonRef, max_depth: usize) -> io::Result<String> {
+    if let RawBsonRef::String(value) = bson_ref {
+        return Ok((*value).to_string());
+    }
+
+    let mut buf = Vec::new();
+    let mut formatter = serde_json::ser::CompactFormatter;
+    let mut path = Vec::new();
+    write_bson_ref(&mut buf, &mut formatter, bson_ref, true, max_depth, 0, &mut path)?;
+    String::from_utf8(buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::ser::CompactFormatter;
+
+    fn raw_doc(doc: bson::Document) -> bson::RawDocumentBuf {
+        bson::RawDocumentBuf::from_document(&doc).expect("valid document")
+    }
+
+    fn write_compact(doc: &bson::RawDocumentBuf, relaxed: bool) -> String {
+        let mut buf = Vec::new();
+        let mut formatter = CompactFormatter;
+        write_document(&mut buf, &mut formatter, doc, relaxed, DEFAULT_MAX_NESTING_DEPTH)
+            .expect("document within depth limit");
+        String::from_utf8(buf).expect("valid utf-8")
+    }
+
+    // The direct RawBsonRef walk must stay byte-compatible with the old
+    // `bson::to_bson(..).into_canonical_extjson()` path it replaced.
+    #[test]
+    fn canonical_matches_bson_crate_extjson() {
+        let doc = bson::doc! {
+            "str": "hello",
+            "int32": 32_i32,
+            "int64": 64_i64,
+            "double": 1.5_f64,
+            "integral_double": 1.0_f64,
+            "large_double": 1e40_f64,
+            "bool": true,
+            "null": bson::Bson::Null,
+            "array": [1_i32, 2_i32, 3_i32],
+            "nested": { "a": 1_i32 },
+            "oid": bson::oid::ObjectId::new(),
+        };
+        let raw = raw_doc(doc.clone());
+
+        let expected = bson::Bson::Document(doc).into_canonical_extjson().to_string();
+        let actual = write_compact(&raw, false);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn relaxed_matches_bson_crate_extjson() {
+        let doc = bson::doc! {
+            "int32": 32_i32,
+            "int64": 64_i64,
+            "double": 1.5_f64,
+        };
+        let raw = raw_doc(doc.clone());
+
+        let expected = bson::Bson::Document(doc).into_relaxed_extjson().to_string();
+        let actual = write_compact(&raw, true);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn depth_guard_reports_offending_path() {
+        let mut doc = bson::doc! { "leaf": 1_i32 };
+        for key in ["c", "b", "a"] {
+            doc = bson::doc! { key: doc };
+        }
+        let raw = raw_doc(doc);
+
+        let mut buf = Vec::new();
+        let mut formatter = CompactFormatter;
+        let error = write_document(&mut buf, &mut formatter, &raw, false, 1)
+            .expect_err("nesting deeper than max_depth must fail");
+
+        assert!(error.to_string().contains("a.b"));
+        assert!(error.to_string().contains("maximum nesting depth of 1"));
+    }
+}
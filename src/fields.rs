@@ -0,0 +1,48 @@
+use bson::{RawBsonRef, RawDocument};
+
+// Resolves a dotted field path (e.g. `a.b.0.c`) against a document, descending
+// through embedded documents and, for numeric segments, arrays. Returns `None`
+// if any segment along the path is missing or of the wrong shape.
+pub fn resolve<'a>(document: &'a RawDocument, path: &str) -> Option<RawBsonRef<'a>> {
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut current = document.get(first).ok().flatten()?;
+
+    for segment in segments {
+        current = match current {
+            RawBsonRef::Document(embedded) => embedded.get(segment).ok().flatten()?,
+            RawBsonRef::Array(embedded) => {
+                let index: usize = segment.parse().ok()?;
+                embedded.get(index).ok().flatten()?
+            }
+            _ => return None,
+        };
+    }
+
+    Some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_nested_field_and_array_index() {
+        let doc = bson::doc! {
+            "a": { "b": [ { "c": 1_i32 }, { "c": 2_i32 } ] },
+        };
+        let raw = bson::RawDocumentBuf::from_document(&doc).expect("valid document");
+
+        let value = resolve(&raw, "a.b.1.c").expect("path resolves");
+        assert_eq!(value.as_i32(), Some(2));
+    }
+
+    #[test]
+    fn missing_segment_returns_none() {
+        let doc = bson::doc! { "a": 1_i32 };
+        let raw = bson::RawDocumentBuf::from_document(&doc).expect("valid document");
+
+        assert!(resolve(&raw, "a.b").is_none());
+        assert!(resolve(&raw, "missing").is_none());
+    }
+}
@@ -1,13 +1,36 @@
-use std::{error::Error, io::Read, result::Result};
+use std::{error::Error, io, io::Read, result::Result};
 
 use bson::RawDocumentBuf;
 
 pub struct RawDocumentBufs<'reader, R: Read> {
     reader: &'reader mut R,
+    skip: usize,
+    skipped: bool,
+    limit: Option<usize>,
+    emitted: usize,
 }
 
 pub fn raw_document_bufs<R: Read>(reader: &mut R) -> RawDocumentBufs<R> {
-    RawDocumentBufs { reader }
+    raw_document_bufs_with_range(reader, 0, None)
+}
+
+// Like `raw_document_bufs`, but skips `skip` leading documents by advancing
+// the reader past their length-prefixed bytes without constructing a
+// `RawDocumentBuf` for them, and stops after emitting `limit` documents (if
+// any), so callers only pay parse/serialize cost for the window they asked
+// for.
+pub fn raw_document_bufs_with_range<R: Read>(
+    reader: &mut R,
+    skip: usize,
+    limit: Option<usize>,
+) -> RawDocumentBufs<R> {
+    RawDocumentBufs {
+        reader,
+        skip,
+        skipped: false,
+        limit,
+        emitted: 0,
+    }
 }
 
 
@@ -36,10 +59,46 @@ impl std::error::Error for BsonSizeError {
     }
 }
 
+impl<'r, R: Read> RawDocumentBufs<'r, R> {
+    // Reads and validates a document's length prefix, then discards the rest
+    // of the document by advancing the reader without allocating a buffer for
+    // it. Returns `Ok(false)` at a clean end-of-stream.
+    fn skip_one(&mut self) -> Result<bool, Box<dyn Error>> {
+        let mut buf: [u8; 4] = [0, 0, 0, 0];
+        if let Err(error) = self.reader.read_exact(&mut buf) {
+            if let std::io::ErrorKind::UnexpectedEof = error.kind() {
+                return Ok(false);
+            } else {
+                return Err(Box::new(error));
+            }
+        }
+        let bson_size = validate_bson_size(i32::from_le_bytes(buf) as usize)?;
+
+        let remaining = (bson_size - buf.len()) as u64;
+        io::copy(&mut self.reader.by_ref().take(remaining), &mut io::sink())?;
+        Ok(true)
+    }
+}
+
 impl<'r, R: Read> std::iter::Iterator for RawDocumentBufs<'r, R> {
     type Item = Result<RawDocumentBuf, Box<dyn Error>>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if !self.skipped {
+            self.skipped = true;
+            for _ in 0..self.skip {
+                match self.skip_one() {
+                    Ok(true) => continue,
+                    Ok(false) => return None,
+                    Err(error) => return Some(Err(error)),
+                }
+            }
+        }
+
+        if matches!(self.limit, Some(limit) if self.emitted >= limit) {
+            return None;
+        }
+
         let mut buf: [u8; 4] = [0, 0, 0, 0];
         if let Err(error) = self.reader.read_exact(&mut buf) {
             if let std::io::ErrorKind::UnexpectedEof = error.kind() {
@@ -48,21 +107,10 @@ impl<'r, R: Read> std::iter::Iterator for RawDocumentBufs<'r, R> {
                 return Some(Err(Box::new(error)));
             }
         }
-        let bson_size = i32::from_le_bytes(buf) as usize;
-
-        if bson_size < MIN_BSON_SIZE {
-            return Some(Err(Box::new(BsonSizeError {
-                size: bson_size,
-                message: String::from("Too small nelly"),
-            })));
-        }
-
-        if bson_size > MAX_BSON_SIZE {
-            return Some(Err(Box::new(BsonSizeError {
-                size: bson_size,
-                message: String::from("Woah nelly"),
-            })));
-        }
+        let bson_size = match validate_bson_size(i32::from_le_bytes(buf) as usize) {
+            Ok(bson_size) => bson_size,
+            Err(error) => return Some(Err(error)),
+        };
 
         let mut remainder = vec![0u8; bson_size - buf.len()];
         if let Err(error) = self.reader.read_exact(&mut remainder) {
@@ -71,6 +119,79 @@ impl<'r, R: Read> std::iter::Iterator for RawDocumentBufs<'r, R> {
 
         let mut bytes = Vec::from(buf);
         bytes.append(&mut remainder);
+
+        self.emitted += 1;
         Some(RawDocumentBuf::from_bytes(bytes).map_err(|e| e.into()))
     }
 }
+
+fn validate_bson_size(bson_size: usize) -> Result<usize, Box<dyn Error>> {
+    if bson_size < MIN_BSON_SIZE {
+        return Err(Box::new(BsonSizeError {
+            size: bson_size,
+            message: String::from("Too small nelly"),
+        }));
+    }
+
+    if bson_size > MAX_BSON_SIZE {
+        return Err(Box::new(BsonSizeError {
+            size: bson_size,
+            message: String::from("Woah nelly"),
+        }));
+    }
+
+    Ok(bson_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn concat_docs(docs: &[bson::Document]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for doc in docs {
+            doc.to_writer(&mut bytes).expect("document serializes");
+        }
+        bytes
+    }
+
+    fn int_docs(values: &[i32]) -> Vec<bson::Document> {
+        values.iter().map(|v| bson::doc! { "v": *v }).collect()
+    }
+
+    #[test]
+    fn yields_all_documents_with_no_range() {
+        let docs = int_docs(&[1, 2, 3]);
+        let mut bytes = concat_docs(&docs).as_slice();
+
+        let values: Vec<i32> = raw_document_bufs(&mut bytes)
+            .map(|doc| doc.expect("valid document").get_i32("v").expect("has v"))
+            .collect();
+
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn skip_advances_past_leading_documents_without_limit() {
+        let docs = int_docs(&[1, 2, 3, 4]);
+        let mut bytes = concat_docs(&docs).as_slice();
+
+        let values: Vec<i32> = raw_document_bufs_with_range(&mut bytes, 2, None)
+            .map(|doc| doc.expect("valid document").get_i32("v").expect("has v"))
+            .collect();
+
+        assert_eq!(values, vec![3, 4]);
+    }
+
+    #[test]
+    fn limit_caps_emitted_documents_after_skip() {
+        let docs = int_docs(&[1, 2, 3, 4, 5]);
+        let mut bytes = concat_docs(&docs).as_slice();
+
+        let values: Vec<i32> = raw_document_bufs_with_range(&mut bytes, 1, Some(2))
+            .map(|doc| doc.expect("valid document").get_i32("v").expect("has v"))
+            .collect();
+
+        assert_eq!(values, vec![2, 3]);
+    }
+}
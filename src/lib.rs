@@ -1,21 +1,27 @@
 use std::error::Error;
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 use std::result::Result;
 
 use bson::{RawArray, RawBsonRef, RawDocument};
 
-use serde::ser::Serialize;
-
-use serde_json::ser::PrettyFormatter;
-use serde_json::value::Value;
-use serde_json::Serializer;
+use serde_json::ser::{CompactFormatter, PrettyFormatter};
 
 mod iter;
-use iter::raw_document_bufs;
+use iter::raw_document_bufs_with_range;
 
 mod bytes;
 use bytes::CountBytes;
 
+mod extjson;
+pub use extjson::DEFAULT_MAX_NESTING_DEPTH;
+
+mod fields;
+
+mod csv;
+
+mod stats;
+use stats::Stats;
+
 type DynResult<T> = Result<T, Box<dyn Error>>;
 type BsonDumpResult<T> = Result<T, BsonDumpError>;
 
@@ -42,27 +48,46 @@ pub struct BsonDump<R: Read, W: Write> {
     reader: R,
     writer: W,
     objcheck: bool,
+    skip: usize,
+    limit: Option<usize>,
+    max_nesting_depth: usize,
     num_found: u32,
 }
 
 impl<R: Read, W: Write> BsonDump<R, W> {
-    pub fn new(reader: R, writer: W, objcheck: bool) -> Self {
+    pub fn new(
+        reader: R,
+        writer: W,
+        objcheck: bool,
+        skip: usize,
+        limit: Option<usize>,
+        max_nesting_depth: usize,
+    ) -> Self {
         BsonDump {
             reader,
             writer,
             objcheck,
+            skip,
+            limit,
+            max_nesting_depth,
             num_found: 0,
         }
     }
 
     pub fn json(mut self) -> BsonDumpResult<u32> {
-        self.print_json(false)
+        self.print_json(false, false)
             .map_err(|e| self.to_bsondump_error(e))?;
         Ok(self.num_found)
     }
 
     pub fn pretty_json(mut self) -> BsonDumpResult<u32> {
-        self.print_json(true)
+        self.print_json(true, false)
+            .map_err(|e| self.to_bsondump_error(e))?;
+        Ok(self.num_found)
+    }
+
+    pub fn relaxed_json(mut self) -> BsonDumpResult<u32> {
+        self.print_json(false, true)
             .map_err(|e| self.to_bsondump_error(e))?;
         Ok(self.num_found)
     }
@@ -72,45 +97,144 @@ impl<R: Read, W: Write> BsonDump<R, W> {
         Ok(self.num_found)
     }
 
-    fn print_pretty_json(writer: &mut W, value: Value, indent: &[u8]) -> DynResult<()> {
-        let formatter = PrettyFormatter::with_indent(indent);
-        let mut ser = Serializer::with_formatter(writer, formatter);
-        value
-            .serialize(&mut ser)
-            .map_err(|err| Box::new(err) as Box<dyn Error>)
+    pub fn csv(mut self, fields: Vec<String>, include_header: bool) -> BsonDumpResult<u32> {
+        self.print_tabular(&fields, b',', include_header)
+            .map_err(|e| self.to_bsondump_error(e))?;
+        Ok(self.num_found)
     }
 
-    fn print_json(&mut self, is_pretty: bool) -> DynResult<()> {
+    pub fn tsv(mut self, fields: Vec<String>, include_header: bool) -> BsonDumpResult<u32> {
+        self.print_tabular(&fields, b'\t', include_header)
+            .map_err(|e| self.to_bsondump_error(e))?;
+        Ok(self.num_found)
+    }
+
+    pub fn stats(mut self) -> BsonDumpResult<u32> {
+        self.print_stats().map_err(|e| self.to_bsondump_error(e))?;
+        Ok(self.num_found)
+    }
+
+    fn print_json(&mut self, is_pretty: bool, is_relaxed: bool) -> DynResult<()> {
         self.num_found = 0;
-        for raw_document_buf in raw_document_bufs(&mut self.reader) {
-            let value = match bson::to_bson(&raw_document_buf?) {
-                Err(error) => {
-                    if !self.objcheck {
-                        continue;
-                    }
-                    return Err(Box::new(error));
-                }
-                Ok(value) => value,
+        // Serialize into a scratch buffer first and only commit it to the
+        // writer once it's known to be complete: `write_document` writes
+        // tokens as it walks the document, and a failure partway through
+        // (malformed element, nesting depth exceeded) must not leak a
+        // truncated, unterminated JSON fragment into the output stream.
+        let mut scratch = Vec::new();
+        for raw_document_buf in raw_document_bufs_with_range(&mut self.reader, self.skip, self.limit) {
+            let raw_document_buf = raw_document_buf?;
+
+            scratch.clear();
+            let result = if is_pretty {
+                let mut formatter = PrettyFormatter::with_indent(b"\t");
+                extjson::write_document(
+                    &mut scratch,
+                    &mut formatter,
+                    &raw_document_buf,
+                    is_relaxed,
+                    self.max_nesting_depth,
+                )
+            } else {
+                let mut formatter = CompactFormatter;
+                extjson::write_document(
+                    &mut scratch,
+                    &mut formatter,
+                    &raw_document_buf,
+                    is_relaxed,
+                    self.max_nesting_depth,
+                )
             };
 
-            let extjson = value.into_canonical_extjson();
+            if let Err(error) = result {
+                if !self.objcheck {
+                    continue;
+                }
+                return Err(Box::new(error));
+            }
 
-            if is_pretty {
-                Self::print_pretty_json(&mut self.writer, extjson, b"\t")?;
-            } else {
-                writeln!(&mut self.writer, "{}", extjson)?;
+            self.writer.write_all(&scratch)?;
+            writeln!(&mut self.writer)?;
+            self.num_found += 1;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn print_tabular(&mut self, fields: &[String], delimiter: u8, include_header: bool) -> DynResult<()> {
+        if fields.is_empty() {
+            return Err(Box::new(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--fields must name at least one field for csv/tsv output",
+            )));
+        }
+
+        self.num_found = 0;
+        if include_header {
+            csv::write_record(&mut self.writer, fields, delimiter)?;
+        }
+        for raw_document_buf in raw_document_bufs_with_range(&mut self.reader, self.skip, self.limit) {
+            let raw_document_buf = raw_document_buf?;
+
+            let mut cells = Vec::with_capacity(fields.len());
+            let mut skip_document = false;
+            for field in fields {
+                let cell = match fields::resolve(&raw_document_buf, field) {
+                    Some(bson_ref) => match extjson::render_field(&bson_ref, self.max_nesting_depth) {
+                        Ok(cell) => cell,
+                        Err(error) => {
+                            if !self.objcheck {
+                                skip_document = true;
+                                break;
+                            }
+                            return Err(Box::new(error));
+                        }
+                    },
+                    None => String::new(),
+                };
+                cells.push(cell);
+            }
+            if skip_document {
+                continue;
             }
+            csv::write_record(&mut self.writer, &cells, delimiter)?;
             self.num_found += 1;
         }
         self.writer.flush()?;
         Ok(())
     }
 
+    fn print_stats(&mut self) -> DynResult<()> {
+        self.num_found = 0;
+        let mut stats = Stats::default();
+        for raw_document_buf in raw_document_bufs_with_range(&mut self.reader, self.skip, self.limit)
+        {
+            let raw_document_buf = raw_document_buf?;
+            if let Err(error) = stats.add_document(&raw_document_buf, self.max_nesting_depth) {
+                if !self.objcheck {
+                    continue;
+                }
+                return Err(Box::new(error));
+            }
+            self.num_found += 1;
+        }
+        stats.write_summary(&mut self.writer)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
     fn print_debug(&mut self) -> DynResult<()> {
         self.num_found = 0;
-        for raw_document_buf in raw_document_bufs(&mut self.reader) {
-            if let Err(error) = Self::print_debug_document(&mut self.writer, &raw_document_buf?, 0)
-            {
+        for raw_document_buf in raw_document_bufs_with_range(&mut self.reader, self.skip, self.limit) {
+            let mut path = Vec::new();
+            if let Err(error) = Self::print_debug_document(
+                &mut self.writer,
+                &raw_document_buf?,
+                0,
+                0,
+                self.max_nesting_depth,
+                &mut path,
+            ) {
                 if !self.objcheck {
                     continue;
                 }
@@ -137,12 +261,20 @@ impl<R: Read, W: Write> BsonDump<R, W> {
         Ok(())
     }
 
-    fn print_debug_array(writer: &mut W, array: &RawArray, indent_level: usize) -> DynResult<()> {
+    fn print_debug_array(
+        writer: &mut W,
+        array: &RawArray,
+        indent_level: usize,
+        depth: usize,
+        max_depth: usize,
+        path: &mut Vec<String>,
+    ) -> DynResult<()> {
+        extjson::check_depth(depth, max_depth, path)?;
         Self::print_new_object_header(writer, array, indent_level)?;
         for (i, element) in array.into_iter().enumerate() {
             let name = i.to_string();
             let bson_ref = element?;
-            Self::print_debug_item(writer, &name, &bson_ref, indent_level)?;
+            Self::print_debug_item(writer, &name, &bson_ref, indent_level, depth, max_depth, path)?;
         }
         Ok(())
     }
@@ -151,11 +283,15 @@ impl<R: Read, W: Write> BsonDump<R, W> {
         writer: &mut W,
         raw_document: &RawDocument,
         indent_level: usize,
+        depth: usize,
+        max_depth: usize,
+        path: &mut Vec<String>,
     ) -> DynResult<()> {
+        extjson::check_depth(depth, max_depth, path)?;
         Self::print_new_object_header(writer, raw_document, indent_level)?;
         for element in raw_document {
             let (name, bson_ref) = element?;
-            Self::print_debug_item(writer, name, &bson_ref, indent_level)?;
+            Self::print_debug_item(writer, name, &bson_ref, indent_level, depth, max_depth, path)?;
         }
         Ok(())
     }
@@ -165,6 +301,9 @@ impl<R: Read, W: Write> BsonDump<R, W> {
         name: &str,
         bson_ref: &RawBsonRef,
         indent_level: usize,
+        depth: usize,
+        max_depth: usize,
+        path: &mut Vec<String>,
     ) -> DynResult<()> {
         writeln!(
             writer,
@@ -182,16 +321,23 @@ impl<R: Read, W: Write> BsonDump<R, W> {
             type = bson_ref.element_type() as u8,
             size = size
         )?;
-        match bson_ref {
-            RawBsonRef::Document(embedded) => {
-                Self::print_debug_document(writer, embedded, indent_level + 3)?
-            }
+        path.push(name.to_string());
+        let result = match bson_ref {
+            RawBsonRef::Document(embedded) => Self::print_debug_document(
+                writer,
+                embedded,
+                indent_level + 3,
+                depth + 1,
+                max_depth,
+                path,
+            ),
             RawBsonRef::Array(embedded) => {
-                Self::print_debug_array(writer, embedded, indent_level + 3)?
+                Self::print_debug_array(writer, embedded, indent_level + 3, depth + 1, max_depth, path)
             }
-            _ => (),
+            _ => Ok(()),
         };
-        Ok(())
+        path.pop();
+        result
     }
 
     fn to_bsondump_error(&self, e: Box<dyn Error>) -> BsonDumpError {
@@ -8,9 +8,7 @@ use clap::{ArgEnum, Parser};
 
 use chrono::{offset::Local, DateTime, TimeZone};
 
-use crate::bsondump::BsonDump;
-
-mod bsondump;
+use bsondump::BsonDump;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
 #[clap(rename_all = "camelCase")]
@@ -18,6 +16,10 @@ enum OutputType {
     Debug,
     Json,
     PrettyJson,
+    RelaxedJson,
+    Csv,
+    Tsv,
+    Stats,
 }
 
 fn print_num_found<Tz>(start: DateTime<Tz>, num_found: u32)
@@ -49,6 +51,26 @@ struct Args {
     #[clap(long = "outFile", name = "outFile", value_parser)]
     /// path to output file to dump JSON to; default is stdout
     out_file: Option<String>,
+
+    #[clap(long, value_parser, value_delimiter = ',')]
+    /// comma-separated list of dotted field paths to project in csv/tsv output
+    fields: Vec<String>,
+
+    #[clap(long = "noHeaderLine", name = "noHeaderLine", value_parser, default_value_t = false)]
+    /// omit the header row of field names from csv/tsv output
+    no_header_line: bool,
+
+    #[clap(long, value_parser, default_value_t = 0)]
+    /// number of leading documents to skip before dumping
+    skip: usize,
+
+    #[clap(long, value_parser)]
+    /// maximum number of documents to dump
+    limit: Option<usize>,
+
+    #[clap(long = "maxNestingDepth", name = "maxNestingDepth", value_parser, default_value_t = bsondump::DEFAULT_MAX_NESTING_DEPTH)]
+    /// maximum depth of embedded documents/arrays before aborting, to guard against pathological nesting
+    max_nesting_depth: usize,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -70,12 +92,23 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     };
 
-    let dump = BsonDump::new(reader, writer, args.objcheck);
+    let dump = BsonDump::new(
+        reader,
+        writer,
+        args.objcheck,
+        args.skip,
+        args.limit,
+        args.max_nesting_depth,
+    );
 
     let start = Local::now();
     let dump_result = match args.output_type {
         OutputType::Json => dump.json(),
         OutputType::PrettyJson => dump.pretty_json(),
+        OutputType::RelaxedJson => dump.relaxed_json(),
+        OutputType::Csv => dump.csv(args.fields, !args.no_header_line),
+        OutputType::Tsv => dump.tsv(args.fields, !args.no_header_line),
+        OutputType::Stats => dump.stats(),
         OutputType::Debug => dump.debug(),
     };
     match dump_result {
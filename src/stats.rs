@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Write;
+
+use bson::{RawArray, RawBsonRef, RawDocument};
+
+use crate::bytes::CountBytes;
+use crate::extjson;
+
+#[derive(Default, Clone, Copy)]
+struct TypeStats {
+    count: u64,
+    bytes: u64,
+}
+
+// Aggregates a per-BSON-type histogram across a whole stream, recursing into
+// embedded documents/arrays, instead of printing each document.
+#[derive(Default)]
+pub struct Stats {
+    documents: u64,
+    total_bytes: u64,
+    by_type: BTreeMap<u8, TypeStats>,
+}
+
+impl Stats {
+    pub fn add_document(&mut self, document: &RawDocument, max_depth: usize) -> io::Result<()> {
+        self.documents += 1;
+        self.total_bytes += document.count_bytes() as u64;
+        let mut path = Vec::new();
+        self.collect_document(document, max_depth, 0, &mut path)
+    }
+
+    fn collect_document(
+        &mut self,
+        document: &RawDocument,
+        max_depth: usize,
+        depth: usize,
+        path: &mut Vec<String>,
+    ) -> io::Result<()> {
+        extjson::check_depth(depth, max_depth, path)?;
+        for element in document {
+            let (name, bson_ref) = element.map_err(extjson::to_io_error)?;
+            path.push(name.to_string());
+            let result = self.record(&bson_ref, max_depth, depth, path);
+            path.pop();
+            result?;
+        }
+        Ok(())
+    }
+
+    fn collect_array(
+        &mut self,
+        array: &RawArray,
+        max_depth: usize,
+        depth: usize,
+        path: &mut Vec<String>,
+    ) -> io::Result<()> {
+        extjson::check_depth(depth, max_depth, path)?;
+        for (i, element) in array.into_iter().enumerate() {
+            let bson_ref = element.map_err(extjson::to_io_error)?;
+            path.push(i.to_string());
+            let result = self.record(&bson_ref, max_depth, depth, path);
+            path.pop();
+            result?;
+        }
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        bson_ref: &RawBsonRef,
+        max_depth: usize,
+        depth: usize,
+        path: &mut Vec<String>,
+    ) -> io::Result<()> {
+        let type_stats = self.by_type.entry(bson_ref.element_type() as u8).or_default();
+        type_stats.count += 1;
+        type_stats.bytes += bson_ref.count_bytes() as u64;
+
+        match bson_ref {
+            RawBsonRef::Document(embedded) => {
+                self.collect_document(embedded, max_depth, depth + 1, path)?
+            }
+            RawBsonRef::Array(embedded) => {
+                self.collect_array(embedded, max_depth, depth + 1, path)?
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    pub fn write_summary<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "documents     : {}", self.documents)?;
+        writeln!(writer, "total bytes   : {}", self.total_bytes)?;
+        writeln!(writer)?;
+        writeln!(writer, "{:<14}{:>12}{:>16}", "type", "count", "bytes")?;
+        for (element_type, type_stats) in &self.by_type {
+            writeln!(
+                writer,
+                "{:<14}{:>12}{:>16}",
+                type_name(*element_type),
+                type_stats.count,
+                type_stats.bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Maps a raw BSON element-type byte (see the BSON spec) to the name used in
+// MongoDB's own tooling, so a stats dump reads as a schema profile rather
+// than a table of opaque integers.
+fn type_name(element_type: u8) -> &'static str {
+    match element_type {
+        1 => "double",
+        2 => "string",
+        3 => "object",
+        4 => "array",
+        5 => "binData",
+        6 => "undefined",
+        7 => "objectId",
+        8 => "bool",
+        9 => "date",
+        10 => "null",
+        11 => "regex",
+        12 => "dbPointer",
+        13 => "javascript",
+        14 => "symbol",
+        15 => "javascriptWithScope",
+        16 => "int",
+        17 => "timestamp",
+        18 => "long",
+        19 => "decimal",
+        127 => "maxKey",
+        255 => "minKey",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_summary_names_types_instead_of_raw_bytes() {
+        let document = bson::RawDocumentBuf::from_document(&bson::doc! {
+            "name": "alice",
+            "age": 30_i32,
+        })
+        .expect("valid document");
+
+        let mut stats = Stats::default();
+        stats
+            .add_document(&document, extjson::DEFAULT_MAX_NESTING_DEPTH)
+            .expect("document within depth limit");
+
+        let mut out = Vec::new();
+        stats.write_summary(&mut out).expect("write succeeds");
+        let summary = String::from_utf8(out).expect("valid utf-8");
+
+        assert!(summary.contains("string"));
+        assert!(summary.contains("int"));
+        assert!(!summary.contains(" 2 "));
+        assert!(!summary.contains(" 16 "));
+    }
+}